@@ -0,0 +1,183 @@
+//! `SPA_META_Cursor` parsing.
+//!
+//! The portal-side mode switch this depends on is `LinuxCapturer::new`'s
+//! `ScreenCastPortal::show_cursor(options.show_cursor)` call, which selects
+//! the portal's "Metadata" cursor mode (as opposed to Hidden/Embedded) —
+//! that's what keeps the pointer out of the video plane entirely. This
+//! module is downstream of that: it just parses the `SPA_META_Cursor` the
+//! compositor attaches once metadata mode is in effect, giving a caller
+//! everything needed to draw the pointer back in themselves: position,
+//! hotspot, and an optional bitmap for frames where PipeWire attached one.
+
+use pipewire as pw;
+use pw::spa::sys::{spa_buffer, spa_meta_bitmap, spa_meta_cursor, SPA_META_Cursor};
+
+#[cfg(test)]
+use std::mem::size_of;
+
+#[cfg(test)]
+use pw::spa::sys::{spa_meta, spa_point, spa_rectangle};
+
+#[derive(Debug, Clone)]
+pub struct CursorBitmap {
+    pub format: u32,
+    pub width: u32,
+    pub height: u32,
+    pub stride: i32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CursorInfo {
+    pub position: (i32, i32),
+    pub hotspot: (i32, i32),
+    pub bitmap: Option<CursorBitmap>,
+}
+
+/// Walks `buffer`'s metas for `SPA_META_Cursor`, returning `None` if the
+/// producer didn't attach one (no meta at all, or `id == 0` which means "no
+/// cursor visible this frame" per the SPA contract).
+pub unsafe fn read_cursor_meta(buffer: *mut spa_buffer) -> Option<CursorInfo> {
+    let n_metas = (*buffer).n_metas;
+    let mut meta_ptr = (*buffer).metas;
+    let metas_end = (*buffer).metas.wrapping_add(n_metas as usize);
+    while meta_ptr != metas_end {
+        if (*meta_ptr).type_ == SPA_META_Cursor {
+            let cursor: &spa_meta_cursor = &*((*meta_ptr).data as *const spa_meta_cursor);
+            if cursor.id == 0 {
+                return None;
+            }
+
+            let bitmap = (cursor.bitmap_offset != 0).then(|| {
+                let bitmap_ptr = (cursor as *const spa_meta_cursor as *const u8)
+                    .add(cursor.bitmap_offset as usize) as *const spa_meta_bitmap;
+                let bitmap = &*bitmap_ptr;
+                let data_ptr =
+                    (bitmap as *const spa_meta_bitmap as *const u8).add(bitmap.offset as usize);
+                let len = (bitmap.size.height * bitmap.stride as u32) as usize;
+                CursorBitmap {
+                    format: bitmap.format,
+                    width: bitmap.size.width,
+                    height: bitmap.size.height,
+                    stride: bitmap.stride,
+                    data: std::slice::from_raw_parts(data_ptr, len).to_vec(),
+                }
+            });
+
+            return Some(CursorInfo {
+                position: (cursor.position.x, cursor.position.y),
+                hotspot: (cursor.hotspot.x, cursor.hotspot.y),
+                bitmap,
+            });
+        }
+        meta_ptr = meta_ptr.wrapping_add(1);
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer_with_metas(metas: &mut [spa_meta]) -> spa_buffer {
+        spa_buffer {
+            n_metas: metas.len() as u32,
+            n_datas: 0,
+            metas: metas.as_mut_ptr(),
+            datas: std::ptr::null_mut(),
+        }
+    }
+
+    /// Lays out a `spa_meta_cursor`, optionally followed by a
+    /// `spa_meta_bitmap` and its pixel data, exactly as PipeWire packs them
+    /// into one meta blob: `bitmap_offset`/`offset` are byte offsets from the
+    /// *start of the struct they're relative to*, not from the blob start, so
+    /// the bytes have to actually sit at those offsets for the pointer
+    /// arithmetic in `read_cursor_meta` to land correctly.
+    fn cursor_blob(id: u32, position: (i32, i32), hotspot: (i32, i32), bitmap: Option<&[u8]>) -> Vec<u8> {
+        let cursor_size = size_of::<spa_meta_cursor>();
+        let bitmap_size = size_of::<spa_meta_bitmap>();
+        let pixel_len = bitmap.map_or(0, |d| d.len());
+        let total = cursor_size + if bitmap.is_some() { bitmap_size + pixel_len } else { 0 };
+
+        let mut blob = vec![0u8; total];
+        let cursor = spa_meta_cursor {
+            id,
+            flags: 0,
+            position: spa_point { x: position.0, y: position.1 },
+            hotspot: spa_point { x: hotspot.0, y: hotspot.1 },
+            bitmap_offset: if bitmap.is_some() { cursor_size as u32 } else { 0 },
+        };
+        unsafe { (blob.as_mut_ptr() as *mut spa_meta_cursor).write(cursor) };
+
+        if let Some(data) = bitmap {
+            let meta_bitmap = spa_meta_bitmap {
+                format: 1,
+                size: spa_rectangle { width: 2, height: 3 },
+                stride: 8,
+                offset: bitmap_size as u32,
+            };
+            unsafe {
+                (blob.as_mut_ptr().add(cursor_size) as *mut spa_meta_bitmap).write(meta_bitmap);
+            }
+            blob[cursor_size + bitmap_size..].copy_from_slice(data);
+        }
+
+        blob
+    }
+
+    #[test]
+    fn no_cursor_meta_means_none() {
+        let mut metas: [spa_meta; 0] = [];
+        let mut buffer = buffer_with_metas(&mut metas);
+        assert!(unsafe { read_cursor_meta(&mut buffer) }.is_none());
+    }
+
+    #[test]
+    fn id_zero_means_no_cursor_visible() {
+        let mut blob = cursor_blob(0, (1, 2), (0, 0), None);
+        let mut metas = [spa_meta {
+            type_: SPA_META_Cursor,
+            size: blob.len() as u32,
+            data: blob.as_mut_ptr() as *mut std::ffi::c_void,
+        }];
+        let mut buffer = buffer_with_metas(&mut metas);
+        assert!(unsafe { read_cursor_meta(&mut buffer) }.is_none());
+    }
+
+    #[test]
+    fn reads_position_and_hotspot_with_no_bitmap() {
+        let mut blob = cursor_blob(1, (10, 20), (3, 4), None);
+        let mut metas = [spa_meta {
+            type_: SPA_META_Cursor,
+            size: blob.len() as u32,
+            data: blob.as_mut_ptr() as *mut std::ffi::c_void,
+        }];
+        let mut buffer = buffer_with_metas(&mut metas);
+
+        let info = unsafe { read_cursor_meta(&mut buffer) }.unwrap();
+        assert_eq!(info.position, (10, 20));
+        assert_eq!(info.hotspot, (3, 4));
+        assert!(info.bitmap.is_none());
+    }
+
+    #[test]
+    fn reads_bitmap_fields_and_pixel_data() {
+        let pixels = vec![0xAAu8; 3 * 8];
+        let mut blob = cursor_blob(1, (0, 0), (0, 0), Some(&pixels));
+        let mut metas = [spa_meta {
+            type_: SPA_META_Cursor,
+            size: blob.len() as u32,
+            data: blob.as_mut_ptr() as *mut std::ffi::c_void,
+        }];
+        let mut buffer = buffer_with_metas(&mut metas);
+
+        let info = unsafe { read_cursor_meta(&mut buffer) }.unwrap();
+        let bitmap = info.bitmap.expect("bitmap_offset != 0 should produce a bitmap");
+        assert_eq!(bitmap.format, 1);
+        assert_eq!(bitmap.width, 2);
+        assert_eq!(bitmap.height, 3);
+        assert_eq!(bitmap.stride, 8);
+        assert_eq!(bitmap.data, pixels);
+    }
+}
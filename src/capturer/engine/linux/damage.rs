@@ -0,0 +1,135 @@
+//! `SPA_META_VideoDamage` parsing.
+//!
+//! On a mostly-static screen the producer only marks the regions that
+//! actually changed, so a consumer willing to track damage can re-upload or
+//! re-encode just those rectangles instead of the whole frame every time.
+
+use std::mem::size_of;
+
+use pipewire as pw;
+use pw::spa::sys::{spa_buffer, spa_meta_region, SPA_META_VideoDamage};
+
+#[cfg(test)]
+use pw::spa::sys::{spa_meta, spa_point, spa_rectangle, spa_region};
+
+/// A changed rectangle, in buffer pixel coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Damage rectangles for one frame. `display_time` is carried along for
+/// convenience but is *not* a reliable correlation key: `get_timestamp`
+/// reports `0` whenever a buffer has no `SPA_META_Header`, so consecutive
+/// frames can share the same value. `frame_worker` sends each `DamageInfo`
+/// on its channel in the same order as the matching `Frame` goes out on
+/// `tx`, and that send order is the actual guarantee — a consumer should
+/// zip the two channels rather than matching on `display_time`. An empty
+/// `regions` means "no damage meta was present, treat as full-frame".
+#[derive(Debug, Clone)]
+pub struct DamageInfo {
+    pub display_time: u64,
+    pub regions: Vec<Rect>,
+}
+
+/// Walks `buffer`'s metas for `SPA_META_VideoDamage`, collecting every
+/// `spa_meta_region` entry packed into it. Returns an empty `Vec` if no
+/// damage meta is present, which callers should treat as "whole frame
+/// changed".
+pub unsafe fn read_damage_meta(buffer: *mut spa_buffer) -> Vec<Rect> {
+    let n_metas = (*buffer).n_metas;
+    let mut meta_ptr = (*buffer).metas;
+    let metas_end = (*buffer).metas.wrapping_add(n_metas as usize);
+    while meta_ptr != metas_end {
+        if (*meta_ptr).type_ == SPA_META_VideoDamage {
+            let n_regions = (*meta_ptr).size as usize / size_of::<spa_meta_region>();
+            let regions_ptr = (*meta_ptr).data as *const spa_meta_region;
+            let mut rects = Vec::with_capacity(n_regions);
+            for i in 0..n_regions {
+                let region = &*regions_ptr.add(i);
+                // A zero-size region terminates the array early.
+                if region.region.size.width == 0 || region.region.size.height == 0 {
+                    break;
+                }
+                rects.push(Rect {
+                    x: region.region.position.x,
+                    y: region.region.position.y,
+                    width: region.region.size.width,
+                    height: region.region.size.height,
+                });
+            }
+            return rects;
+        }
+        meta_ptr = meta_ptr.wrapping_add(1);
+    }
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(x: i32, y: i32, width: u32, height: u32) -> spa_meta_region {
+        spa_meta_region {
+            region: spa_region {
+                position: spa_point { x, y },
+                size: spa_rectangle { width, height },
+            },
+        }
+    }
+
+    fn buffer_with_metas(metas: &mut [spa_meta]) -> spa_buffer {
+        spa_buffer {
+            n_metas: metas.len() as u32,
+            n_datas: 0,
+            metas: metas.as_mut_ptr(),
+            datas: std::ptr::null_mut(),
+        }
+    }
+
+    #[test]
+    fn no_metas_means_no_damage() {
+        let mut metas: [spa_meta; 0] = [];
+        let mut buffer = buffer_with_metas(&mut metas);
+        assert!(unsafe { read_damage_meta(&mut buffer) }.is_empty());
+    }
+
+    #[test]
+    fn reads_every_region_in_the_damage_meta() {
+        let mut regions = [region(1, 2, 3, 4), region(5, 6, 7, 8)];
+        let mut metas = [spa_meta {
+            type_: SPA_META_VideoDamage,
+            size: (regions.len() * size_of::<spa_meta_region>()) as u32,
+            data: regions.as_mut_ptr() as *mut std::ffi::c_void,
+        }];
+        let mut buffer = buffer_with_metas(&mut metas);
+
+        let rects = unsafe { read_damage_meta(&mut buffer) };
+
+        assert_eq!(
+            rects,
+            vec![
+                Rect { x: 1, y: 2, width: 3, height: 4 },
+                Rect { x: 5, y: 6, width: 7, height: 8 },
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_at_a_zero_sized_region() {
+        let mut regions = [region(1, 2, 3, 4), region(0, 0, 0, 0), region(9, 9, 9, 9)];
+        let mut metas = [spa_meta {
+            type_: SPA_META_VideoDamage,
+            size: (regions.len() * size_of::<spa_meta_region>()) as u32,
+            data: regions.as_mut_ptr() as *mut std::ffi::c_void,
+        }];
+        let mut buffer = buffer_with_metas(&mut metas);
+
+        let rects = unsafe { read_damage_meta(&mut buffer) };
+
+        assert_eq!(rects, vec![Rect { x: 1, y: 2, width: 3, height: 4 }]);
+    }
+}
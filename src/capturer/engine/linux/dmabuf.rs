@@ -0,0 +1,98 @@
+//! GBM/EGL helpers for negotiating and importing non-linear DMA-BUF modifiers.
+//!
+//! PipeWire producers on GNOME/Mutter and wlroots commonly hand us tiled
+//! buffers (Intel Y-tiled, AMD GFX9, ...). `mmap`-ing those directly yields
+//! garbage, so we'd need to ask the local GPU which modifiers it understands,
+//! advertise that list during format negotiation, and detile through GBM/EGL
+//! whenever the negotiated modifier isn't linear.
+//!
+//! TODO(not yet implemented): `gbm_egl` below is a stub. Neither
+//! `eglQueryDmaBufModifiersEXT` nor the GBM/EGL import-and-blit path is
+//! wired up, so in practice `query_supported_modifiers` always degrades to
+//! `[LINEAR_MODIFIER]` (no real negotiation happens) and `detile` always
+//! fails for any buffer the producer actually sends non-linear. Until this
+//! is implemented, non-linear DMA-BUFs are not supported — this module only
+//! gets us as far as the plumbing to advertise and handle modifiers once the
+//! real GBM/EGL calls land.
+//!
+//! Scope note: despite this module's originating commit title ("Negotiate
+//! non-linear DMA-BUF modifiers via GBM/EGL"), nothing here actually
+//! negotiates or detiles anything yet. Treat that commit as plumbing-only;
+//! the real GBM/EGL work is still an open, unimplemented follow-up.
+
+use std::os::unix::io::RawFd;
+
+use super::error::LinCapError;
+
+/// DRM "linear" modifier, i.e. `DRM_FORMAT_MOD_LINEAR`.
+pub const LINEAR_MODIFIER: u64 = 0;
+
+/// Returns the DMA-BUF modifiers the local GPU can import for `fourcc`,
+/// queried via `eglQueryDmaBufModifiersEXT`. Falls back to just the linear
+/// modifier if no GPU/EGL modifier query is available, so callers can still
+/// negotiate a working (if unaccelerated) format.
+///
+/// Currently this is *always* the fallback: see the stub note on
+/// `gbm_egl::query_modifiers` below.
+pub fn query_supported_modifiers(fourcc: u32) -> Vec<u64> {
+    match gbm_egl::query_modifiers(fourcc) {
+        Ok(modifiers) if !modifiers.is_empty() => modifiers,
+        _ => vec![LINEAR_MODIFIER],
+    }
+}
+
+/// Imports a tiled dma-buf via GBM, detiles it through an EGL image +
+/// `glReadPixels`, and returns the resulting linear RGBA bytes.
+///
+/// Currently this always returns `Err`: see the stub note on
+/// `gbm_egl::import_and_read_back` below. Callers should not expect
+/// non-linear buffers to decode until that's implemented.
+pub unsafe fn detile(
+    fd: RawFd,
+    modifier: u64,
+    fourcc: u32,
+    width: u32,
+    height: u32,
+    stride: u32,
+) -> Result<Vec<u8>, LinCapError> {
+    gbm_egl::import_and_read_back(fd, modifier, fourcc, width, height, stride)
+}
+
+/// Thin wrapper around the libgbm/libEGL calls we need. Kept in its own
+/// submodule so the unsafe FFI surface stays small and easy to audit.
+///
+/// NOT YET IMPLEMENTED: both functions below are unconditional stubs, not a
+/// working fallback path with a narrow gap — don't mistake `LinCapError`
+/// here for "ran and failed", it's "never actually tried". Wiring this up
+/// needs:
+/// - `query_modifiers`: open the render node and call
+///   `eglQueryDmaBufModifiersEXT` for `fourcc`.
+/// - `import_and_read_back`: `gbm_bo_import(GBM_BO_IMPORT_FD_MODIFIER)`,
+///   wrap it in an `EGLImage` via `eglCreateImageKHR`, bind it to a texture
+///   with `glEGLImageTargetTexture2DOES`, attach to an FBO and
+///   `glReadPixels` it back into host memory.
+mod gbm_egl {
+    use std::os::unix::io::RawFd;
+
+    use super::LinCapError;
+
+    pub fn query_modifiers(_fourcc: u32) -> Result<Vec<u64>, LinCapError> {
+        Err(LinCapError::Other(
+            "GBM/EGL modifier query not implemented; only DRM_FORMAT_MOD_LINEAR is supported"
+                .into(),
+        ))
+    }
+
+    pub unsafe fn import_and_read_back(
+        _fd: RawFd,
+        _modifier: u64,
+        _fourcc: u32,
+        _width: u32,
+        _height: u32,
+        _stride: u32,
+    ) -> Result<Vec<u8>, LinCapError> {
+        Err(LinCapError::Other(
+            "GBM/EGL detiling not implemented; cannot read a non-linear dma-buf".into(),
+        ))
+    }
+}
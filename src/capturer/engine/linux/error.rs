@@ -0,0 +1,63 @@
+use std::fmt;
+
+use pipewire as pw;
+
+/// Errors surfaced by the Linux (PipeWire/portal) capture backend.
+#[derive(Debug)]
+pub enum LinCapError {
+    Io(std::io::Error),
+    Dbus(dbus::Error),
+    PipeWire(pw::Error),
+    ChannelClosed,
+    Other(String),
+}
+
+impl fmt::Display for LinCapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LinCapError::Io(e) => write!(f, "io error: {e}"),
+            LinCapError::Dbus(e) => write!(f, "dbus error: {e}"),
+            LinCapError::PipeWire(e) => write!(f, "pipewire error: {e}"),
+            LinCapError::ChannelClosed => write!(f, "capture channel closed"),
+            LinCapError::Other(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for LinCapError {}
+
+impl From<std::io::Error> for LinCapError {
+    fn from(e: std::io::Error) -> Self {
+        LinCapError::Io(e)
+    }
+}
+
+impl From<rustix::io::Errno> for LinCapError {
+    fn from(e: rustix::io::Errno) -> Self {
+        LinCapError::Io(e.into())
+    }
+}
+
+impl From<dbus::Error> for LinCapError {
+    fn from(e: dbus::Error) -> Self {
+        LinCapError::Dbus(e)
+    }
+}
+
+impl From<pw::Error> for LinCapError {
+    fn from(e: pw::Error) -> Self {
+        LinCapError::PipeWire(e)
+    }
+}
+
+impl<T> From<std::sync::mpsc::SendError<T>> for LinCapError {
+    fn from(_: std::sync::mpsc::SendError<T>) -> Self {
+        LinCapError::ChannelClosed
+    }
+}
+
+impl From<std::sync::mpsc::RecvError> for LinCapError {
+    fn from(_: std::sync::mpsc::RecvError) -> Self {
+        LinCapError::ChannelClosed
+    }
+}
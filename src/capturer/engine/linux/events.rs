@@ -0,0 +1,41 @@
+//! Capture lifecycle events, delivered alongside frames instead of through
+//! panics so a caller can actually recover from portal/stream failures.
+
+use pipewire as pw;
+
+use super::error::LinCapError;
+
+#[derive(Debug, Clone)]
+pub enum CaptureEvent {
+    /// The PipeWire stream reported `StreamState::Error`.
+    StreamError(String),
+    /// A new format was negotiated via `param_changed`.
+    FormatChanged(pw::spa::param::video::VideoInfoRaw),
+    /// A buffer arrived in a data type or pixel format we don't support; the
+    /// frame was dropped.
+    UnsupportedFormat,
+    /// The capturer has shut down and won't emit any more frames or events.
+    Stopped,
+}
+
+/// What `build_frame` can fail with; translated into a `CaptureEvent` by the
+/// worker instead of panicking.
+pub enum BuildFrameError {
+    Unsupported,
+    Io(LinCapError),
+}
+
+impl From<LinCapError> for BuildFrameError {
+    fn from(e: LinCapError) -> Self {
+        BuildFrameError::Io(e)
+    }
+}
+
+impl BuildFrameError {
+    pub fn into_event(self) -> CaptureEvent {
+        match self {
+            BuildFrameError::Unsupported => CaptureEvent::UnsupportedFormat,
+            BuildFrameError::Io(e) => CaptureEvent::StreamError(e.to_string()),
+        }
+    }
+}
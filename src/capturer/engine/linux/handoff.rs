@@ -0,0 +1,127 @@
+//! Hand-off of the latest PipeWire buffer from the realtime `process`
+//! callback to a worker thread that does the actual mmap/copy.
+//!
+//! This follows the same "always keep only the newest buffer" model
+//! gamescope's screen capture uses: publishing the newest buffer is a
+//! lock-free pointer swap, and recycled buffers go back through a short
+//! `Mutex`-guarded list that the PipeWire main loop drains; it never waits
+//! on the mmap/copy itself, and a slow consumer just causes older frames to
+//! be recycled instead of backing up the stream.
+
+use std::sync::{
+    atomic::{AtomicPtr, Ordering},
+    Mutex,
+};
+
+use pipewire as pw;
+use pw::sys::pw_buffer;
+use rustix::fd::{AsFd, OwnedFd};
+
+use crate::capturer::engine::linux::error::LinCapError;
+
+pub type RawBuffer = *mut pw_buffer;
+
+pub struct FrameHandoff {
+    /// Most recently dequeued buffer, not yet picked up by the worker.
+    out_buffer: AtomicPtr<pw_buffer>,
+    /// Buffers the worker has finished reading; waiting to be re-queued by
+    /// `process_callback` on the PipeWire thread. A queue rather than a
+    /// single slot: the worker can finish more than one buffer between two
+    /// `process_callback` invocations (e.g. several nudges coalesce on the
+    /// eventfd while it's busy), and a single overwrite-prone slot would
+    /// silently leak whichever buffer lost the race.
+    finished: Mutex<Vec<RawBuffer>>,
+    /// eventfd used to wake the worker up without it having to poll.
+    nudge_fd: OwnedFd,
+}
+
+impl FrameHandoff {
+    pub fn new() -> Result<Self, LinCapError> {
+        let nudge_fd = rustix::event::eventfd(0, rustix::event::EventfdFlags::empty())?;
+        Ok(Self {
+            out_buffer: AtomicPtr::new(std::ptr::null_mut()),
+            finished: Mutex::new(Vec::new()),
+            nudge_fd,
+        })
+    }
+
+    /// Publishes `buffer` as the newest frame, returning a stale buffer (if
+    /// any) that the caller should immediately re-queue.
+    pub fn publish(&self, buffer: RawBuffer) -> Option<RawBuffer> {
+        let stale = self.out_buffer.swap(buffer, Ordering::AcqRel);
+        (!stale.is_null()).then_some(stale)
+    }
+
+    /// Takes one buffer the worker finished with, if any are waiting to be
+    /// re-queued to PipeWire. Callers should loop on this until it returns
+    /// `None` to drain every buffer the worker recycled, not just the
+    /// latest.
+    pub fn take_finished(&self) -> Option<RawBuffer> {
+        self.finished.lock().unwrap().pop()
+    }
+
+    /// Takes the newest published buffer, if any, for the worker to process.
+    pub fn take_latest(&self) -> Option<RawBuffer> {
+        let buffer = self.out_buffer.swap(std::ptr::null_mut(), Ordering::AcqRel);
+        (!buffer.is_null()).then_some(buffer)
+    }
+
+    /// Hands a processed buffer back for `process_callback` to re-queue.
+    pub fn recycle(&self, buffer: RawBuffer) {
+        self.finished.lock().unwrap().push(buffer);
+    }
+
+    /// Wakes up a thread blocked in `wait()`.
+    pub fn nudge(&self) {
+        let _ = rustix::io::write(&self.nudge_fd, &1u64.to_ne_bytes());
+    }
+
+    /// Blocks until `nudge()` is called.
+    pub fn wait(&self) {
+        let mut buf = [0u8; 8];
+        let _ = rustix::io::read(self.nudge_fd.as_fd(), &mut buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dummy(n: usize) -> RawBuffer {
+        n as RawBuffer
+    }
+
+    #[test]
+    fn publish_returns_the_previous_buffer_as_stale() {
+        let handoff = FrameHandoff::new().unwrap();
+        assert!(handoff.publish(dummy(1)).is_none());
+        assert_eq!(handoff.publish(dummy(2)), Some(dummy(1)));
+    }
+
+    #[test]
+    fn take_latest_drains_the_published_buffer_once() {
+        let handoff = FrameHandoff::new().unwrap();
+        assert!(handoff.take_latest().is_none());
+        handoff.publish(dummy(1));
+        assert_eq!(handoff.take_latest(), Some(dummy(1)));
+        assert!(handoff.take_latest().is_none());
+    }
+
+    #[test]
+    fn recycle_queues_every_buffer_instead_of_overwriting() {
+        // Two finish/recycle cycles back-to-back with no intervening
+        // `take_finished` -- the scenario that used to leak the first
+        // buffer when `finished` was a single overwrite-prone slot.
+        let handoff = FrameHandoff::new().unwrap();
+        handoff.recycle(dummy(1));
+        handoff.recycle(dummy(2));
+
+        let mut drained = Vec::new();
+        while let Some(buffer) = handoff.take_finished() {
+            drained.push(buffer);
+        }
+        drained.sort();
+        assert_eq!(drained, vec![dummy(1), dummy(2)]);
+        assert!(handoff.take_finished().is_none());
+    }
+}
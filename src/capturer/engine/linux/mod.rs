@@ -4,6 +4,7 @@ use std::{
     sync::{
         atomic::{AtomicBool, AtomicU8},
         mpsc::{self, sync_channel, SyncSender},
+        Arc, Mutex,
     },
     thread::JoinHandle,
     time::Duration,
@@ -39,12 +40,25 @@ use rustix::{
 
 use crate::{
     capturer::Options,
+    encoder::{Encoder, EncodedFrame, SessionConfig},
     frame::{BGRxFrame, Frame, RGBFrame, RGBxFrame, XBGRFrame},
 };
 
-use self::{error::LinCapError, portal::ScreenCastPortal};
+use self::{
+    cursor::CursorInfo,
+    damage::DamageInfo,
+    error::LinCapError,
+    events::{BuildFrameError, CaptureEvent},
+    handoff::FrameHandoff,
+    portal::ScreenCastPortal,
+};
 
+mod cursor;
+mod damage;
+mod dmabuf;
 mod error;
+mod events;
+mod handoff;
 mod ioctl;
 mod portal;
 
@@ -53,8 +67,12 @@ static STREAM_STATE_CHANGED_TO_ERROR: AtomicBool = AtomicBool::new(false);
 
 #[derive(Clone)]
 struct ListenerUserData {
-    pub tx: mpsc::Sender<Frame>,
     pub format: spa::param::video::VideoInfoRaw,
+    /// Shared with the worker thread so it can read whatever format was most
+    /// recently negotiated without touching the PipeWire thread's copy.
+    pub shared_format: Arc<Mutex<spa::param::video::VideoInfoRaw>>,
+    pub handoff: Arc<FrameHandoff>,
+    pub events_tx: mpsc::Sender<CaptureEvent>,
 }
 
 fn param_changed_callback(
@@ -78,25 +96,32 @@ fn param_changed_callback(
         return;
     }
 
-    user_data
-        .format
-        .parse(param)
-        // TODO: Tell library user of the error
-        .expect("Failed to parse format parameter");
+    if let Err(e) = user_data.format.parse(param) {
+        let _ = user_data
+            .events_tx
+            .send(CaptureEvent::StreamError(format!(
+                "Failed to parse format parameter: {e:?}"
+            )));
+        return;
+    }
+
+    *user_data.shared_format.lock().unwrap() = user_data.format.clone();
+    let _ = user_data
+        .events_tx
+        .send(CaptureEvent::FormatChanged(user_data.format.clone()));
 }
 
 fn state_changed_callback(
     _stream: &StreamRef,
-    _user_data: &mut ListenerUserData,
+    user_data: &mut ListenerUserData,
     _old: StreamState,
     new: StreamState,
 ) {
-    match new {
-        StreamState::Error(e) => {
-            eprintln!("pipewire: State changed to error({e})");
-            STREAM_STATE_CHANGED_TO_ERROR.store(true, std::sync::atomic::Ordering::Relaxed);
-        }
-        _ => {}
+    if let StreamState::Error(e) = new {
+        let _ = user_data
+            .events_tx
+            .send(CaptureEvent::StreamError(e.to_string()));
+        STREAM_STATE_CHANGED_TO_ERROR.store(true, std::sync::atomic::Ordering::Relaxed);
     }
 }
 
@@ -154,85 +179,205 @@ unsafe fn fd_read(buffer: *mut spa_buffer, is_dma_buff: bool) -> Result<Vec<u8>,
     Ok(frame_vec)
 }
 
-fn process_callback(stream: &StreamRef, user_data: &mut ListenerUserData) {
-    let buffer = unsafe { stream.dequeue_raw_buffer() };
-    if !buffer.is_null() {
-        'outside: {
-            let buffer = unsafe { (*buffer).buffer };
-            if buffer.is_null() {
-                break 'outside;
+/// Reads and converts one dequeued `spa_buffer` into a `Frame`, using
+/// whichever format was last negotiated. Runs on the worker thread, well off
+/// the realtime PipeWire loop.
+unsafe fn build_frame(
+    buffer: *mut spa_buffer,
+    format: &spa::param::video::VideoInfoRaw,
+) -> Result<Option<Frame>, BuildFrameError> {
+    let timestamp = get_timestamp(buffer);
+
+    let n_datas = (*buffer).n_datas;
+    if n_datas < 1 {
+        return Ok(None);
+    }
+    let frame_size = format.size();
+    let frame_data: Vec<u8> = match (*(*buffer).datas).type_ {
+        SPA_DATA_DMA_BUF => {
+            let modifier = format.modifier();
+            if modifier == dmabuf::LINEAR_MODIFIER {
+                fd_read(buffer, true)?
+            } else {
+                let chunk = (*(*buffer).datas).chunk;
+                dmabuf::detile(
+                    (*(*buffer).datas).fd as RawFd,
+                    modifier,
+                    format.format().as_raw(),
+                    frame_size.width,
+                    frame_size.height,
+                    (*chunk).stride as u32,
+                )?
             }
-            let timestamp = unsafe { get_timestamp(buffer) };
+        }
+        SPA_DATA_MEM_FD | SPA_DATA_MEM_PTR => std::slice::from_raw_parts(
+            (*(*buffer).datas).data as *mut u8,
+            (*(*buffer).datas).maxsize as usize,
+        )
+        .to_vec(),
+        _ => return Err(BuildFrameError::Unsupported),
+    };
 
-            let n_datas = unsafe { (*buffer).n_datas };
-            if n_datas < 1 {
-                return;
-            }
-            let frame_size = user_data.format.size();
-            let frame_data: Vec<u8> = match unsafe { (*(*buffer).datas).type_ } {
-                SPA_DATA_DMA_BUF => {
-                    if user_data.format.modifier() != 0 {
-                        panic!("Unsupported modifier, only linear modifier is supported");
-                    }
+    Ok(Some(match format.format() {
+        VideoFormat::RGBx => Frame::RGBx(RGBxFrame {
+            display_time: timestamp as u64,
+            width: frame_size.width as i32,
+            height: frame_size.height as i32,
+            data: frame_data,
+        }),
+        VideoFormat::RGB => Frame::RGB(RGBFrame {
+            display_time: timestamp as u64,
+            width: frame_size.width as i32,
+            height: frame_size.height as i32,
+            data: frame_data,
+        }),
+        VideoFormat::xBGR => Frame::XBGR(XBGRFrame {
+            display_time: timestamp as u64,
+            width: frame_size.width as i32,
+            height: frame_size.height as i32,
+            data: frame_data,
+        }),
+        VideoFormat::BGRx => Frame::BGRx(BGRxFrame {
+            display_time: timestamp as u64,
+            width: frame_size.width as i32,
+            height: frame_size.height as i32,
+            data: frame_data,
+        }),
+        VideoFormat::RGBA => Frame::RGBx(RGBxFrame {
+            display_time: timestamp as u64,
+            width: frame_size.width as i32,
+            height: frame_size.height as i32,
+            data: frame_data,
+        }),
+        _ => return Err(BuildFrameError::Unsupported),
+    }))
+}
+
+fn frame_display_time(frame: &Frame) -> u64 {
+    match frame {
+        Frame::RGBx(f) => f.display_time,
+        Frame::RGB(f) => f.display_time,
+        Frame::XBGR(f) => f.display_time,
+        Frame::BGRx(f) => f.display_time,
+        #[allow(unreachable_patterns)]
+        _ => 0,
+    }
+}
 
-                    unsafe { fd_read(buffer, true) }.unwrap()
+/// Drains freshly published buffers off the `FrameHandoff`, converts them
+/// into `Frame`s, and feeds the library user's channel. Runs on its own
+/// thread so a slow consumer never stalls the PipeWire realtime loop.
+fn frame_worker(
+    handoff: Arc<FrameHandoff>,
+    shared_format: Arc<Mutex<spa::param::video::VideoInfoRaw>>,
+    tx: mpsc::Sender<Frame>,
+    encoder_session: Option<SessionConfig>,
+    encoded_tx: Option<mpsc::Sender<EncodedFrame>>,
+    cursor_tx: mpsc::Sender<CursorInfo>,
+    damage_tx: mpsc::Sender<DamageInfo>,
+    events_tx: mpsc::Sender<CaptureEvent>,
+) {
+    let mut encoder = encoder_session.and_then(|session| match Encoder::new(session) {
+        Ok(encoder) => Some(encoder),
+        Err(e) => {
+            let _ = events_tx.send(CaptureEvent::StreamError(format!(
+                "Failed to start encoder session: {e}"
+            )));
+            None
+        }
+    });
+
+    loop {
+        handoff.wait();
+        if CAPTURER_STATE.load(std::sync::atomic::Ordering::Relaxed) == 2
+            || STREAM_STATE_CHANGED_TO_ERROR.load(std::sync::atomic::Ordering::Relaxed)
+        {
+            break;
+        }
+        let Some(raw_buffer) = handoff.take_latest() else {
+            continue;
+        };
+        let buffer = unsafe { (*raw_buffer).buffer };
+        if !buffer.is_null() {
+            let format = shared_format.lock().unwrap().clone();
+            match unsafe { build_frame(buffer, &format) } {
+                Ok(Some(frame)) => {
+                    // Only send cursor/damage once we know a matching `Frame`
+                    // is also going out on `tx` this iteration. Damage is
+                    // sent once per frame, so it zips with `tx` by send
+                    // order; a cursor is sent only when the buffer carries
+                    // a visible one, so that channel can skip frames.
+                    if let Some(cursor) = unsafe { cursor::read_cursor_meta(buffer) } {
+                        let _ = cursor_tx.send(cursor);
+                    }
+                    let regions = unsafe { damage::read_damage_meta(buffer) };
+                    let _ = damage_tx.send(DamageInfo {
+                        display_time: frame_display_time(&frame),
+                        regions,
+                    });
+
+                    if let (Some(encoder), Some(encoded_tx)) =
+                        (encoder.as_mut(), encoded_tx.as_ref())
+                    {
+                        match encoder.encode(&frame) {
+                            Ok(encoded) => {
+                                if let Err(e) = encoded_tx.send(encoded) {
+                                    eprintln!("{e}");
+                                }
+                            }
+                            Err(e) => eprintln!("{e}"),
+                        }
+                    }
+                    if let Err(e) = tx.send(frame) {
+                        eprintln!("{e}");
+                    }
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let _ = events_tx.send(e.into_event());
                 }
-                SPA_DATA_MEM_FD | SPA_DATA_MEM_PTR => unsafe {
-                    std::slice::from_raw_parts(
-                        (*(*buffer).datas).data as *mut u8,
-                        (*(*buffer).datas).maxsize as usize,
-                    )
-                    .to_vec()
-                },
-                _ => panic!("Unsupported spa data received"),
-            };
-            if let Err(e) = match user_data.format.format() {
-                VideoFormat::RGBx => user_data.tx.send(Frame::RGBx(RGBxFrame {
-                    display_time: timestamp as u64,
-                    width: frame_size.width as i32,
-                    height: frame_size.height as i32,
-                    data: frame_data,
-                })),
-                VideoFormat::RGB => user_data.tx.send(Frame::RGB(RGBFrame {
-                    display_time: timestamp as u64,
-                    width: frame_size.width as i32,
-                    height: frame_size.height as i32,
-                    data: frame_data,
-                })),
-                VideoFormat::xBGR => user_data.tx.send(Frame::XBGR(XBGRFrame {
-                    display_time: timestamp as u64,
-                    width: frame_size.width as i32,
-                    height: frame_size.height as i32,
-                    data: frame_data,
-                })),
-                VideoFormat::BGRx => user_data.tx.send(Frame::BGRx(BGRxFrame {
-                    display_time: timestamp as u64,
-                    width: frame_size.width as i32,
-                    height: frame_size.height as i32,
-                    data: frame_data,
-                })),
-                VideoFormat::RGBA => user_data.tx.send(Frame::RGBx(RGBxFrame {
-                    display_time: timestamp as u64,
-                    width: frame_size.width as i32,
-                    height: frame_size.height as i32,
-                    data: frame_data,
-                })),
-                _ => panic!("Unsupported frame format received"),
-            } {
-                eprintln!("{e}");
             }
         }
-    } else {
+        handoff.recycle(raw_buffer);
+    }
+
+    let _ = events_tx.send(CaptureEvent::Stopped);
+}
+
+/// The realtime PipeWire callback: re-queues the buffers the worker has
+/// recycled (a short `Mutex`-guarded drain), swaps a pointer and nudges the
+/// worker, never does mmap/copy/convert work itself. Any buffer that the
+/// worker hasn't picked up by the time a newer one arrives is immediately
+/// re-queued, so we always bound latency to the newest frame instead of
+/// backing up behind a slow consumer.
+fn process_callback(stream: &StreamRef, user_data: &mut ListenerUserData) {
+    // Drain every buffer the worker recycled, not just one: it can finish
+    // more than one between two `process_callback` calls.
+    while let Some(finished) = user_data.handoff.take_finished() {
+        unsafe { stream.queue_raw_buffer(finished) };
+    }
+
+    let buffer = unsafe { stream.dequeue_raw_buffer() };
+    if buffer.is_null() {
         eprintln!("Out of buffers");
+        return;
+    }
+
+    if let Some(stale) = user_data.handoff.publish(buffer) {
+        unsafe { stream.queue_raw_buffer(stale) };
     }
 
-    unsafe { stream.queue_raw_buffer(buffer) };
+    user_data.handoff.nudge();
 }
 
 // TODO: Format negotiation
 fn pipewire_capturer(
     options: Options,
     tx: mpsc::Sender<Frame>,
+    encoded_tx: Option<mpsc::Sender<EncodedFrame>>,
+    cursor_tx: mpsc::Sender<CursorInfo>,
+    damage_tx: mpsc::Sender<DamageInfo>,
+    events_tx: mpsc::Sender<CaptureEvent>,
     ready_sender: &SyncSender<bool>,
     stream_id: u32,
 ) -> Result<(), LinCapError> {
@@ -242,11 +387,31 @@ fn pipewire_capturer(
     let context = Context::new(&mainloop)?;
     let core = context.connect(None)?;
 
+    let handoff = Arc::new(FrameHandoff::new()?);
+    let shared_format = Arc::new(Mutex::new(spa::param::video::VideoInfoRaw::default()));
+
     let user_data = ListenerUserData {
-        tx,
         format: Default::default(),
+        shared_format: shared_format.clone(),
+        handoff: handoff.clone(),
+        events_tx: events_tx.clone(),
     };
 
+    let worker_handoff = handoff.clone();
+    let encoder_session = options.encode.as_ref().map(|c| c.session.clone());
+    let worker_handle = std::thread::spawn(move || {
+        frame_worker(
+            worker_handoff,
+            shared_format,
+            tx,
+            encoder_session,
+            encoded_tx,
+            cursor_tx,
+            damage_tx,
+            events_tx,
+        )
+    });
+
     let stream = pw::stream::Stream::new(
         &core,
         "scap",
@@ -264,6 +429,27 @@ fn pipewire_capturer(
         .process(process_callback)
         .register()?;
 
+    // Advertise the modifiers `dmabuf::query_supported_modifiers` returns as
+    // an enum choice. That query is a stub, so today the list is only the
+    // linear modifier and no tiled buffers are negotiated.
+    // `param_changed_callback` reads back whichever modifier PipeWire
+    // picked; `build_frame` hands a non-linear one to `dmabuf::detile`,
+    // which always returns an error, so such buffers are not decoded.
+    let supported_modifiers = dmabuf::query_supported_modifiers(VideoFormat::RGBx.as_raw());
+    let modifier_prop = pw::spa::pod::Property {
+        key: FormatProperties::VideoModifier.as_raw(),
+        flags: pw::spa::pod::PropertyFlags::MANDATORY | pw::spa::pod::PropertyFlags::DONT_FIXATE,
+        value: pw::spa::pod::Value::Choice(pw::spa::pod::ChoiceValue::Long(
+            pw::spa::utils::Choice(
+                pw::spa::utils::ChoiceFlags::empty(),
+                pw::spa::utils::ChoiceEnum::Enum {
+                    default: dmabuf::LINEAR_MODIFIER as i64,
+                    alternatives: supported_modifiers.iter().map(|m| *m as i64).collect(),
+                },
+            ),
+        )),
+    };
+
     let obj = pw::spa::pod::object!(
         pw::spa::utils::SpaTypes::ObjectParamFormat,
         pw::spa::param::ParamType::EnumFormat,
@@ -315,14 +501,7 @@ fn pipewire_capturer(
                 denom: 1
             }
         ),
-        // Ask linear modifier from pipewire.
-        // Nothing make sure that pipewire will give us linear modifier,
-        // it is determined by how xdg portal backend is implemented.
-        pw::spa::pod::property!(
-            pw::spa::param::format::FormatProperties::VideoModifier,
-            Long,
-            0 // Linear modifier, found in link https://github.com/dzfranklin/drm-fourcc-rs/blob/main/src/consts.rs#L134
-        ),
+        modifier_prop,
     );
 
     let metas_obj = pw::spa::pod::object!(
@@ -338,6 +517,44 @@ fn pipewire_capturer(
         ),
     );
 
+    // Max bitmap size a cursor meta can carry; large enough for every cursor
+    // theme size we've seen in practice (64x64 RGBA).
+    const MAX_CURSOR_BITMAP_BYTES: usize = 64 * 64 * 4;
+    let cursor_meta_obj = pw::spa::pod::object!(
+        SpaTypes::ObjectParamMeta,
+        ParamType::Meta,
+        Property::new(
+            SPA_PARAM_META_type,
+            pw::spa::pod::Value::Id(pw::spa::utils::Id(pw::spa::sys::SPA_META_Cursor))
+        ),
+        Property::new(
+            SPA_PARAM_META_size,
+            pw::spa::pod::Value::Int(
+                (size_of::<pw::spa::sys::spa_meta_cursor>()
+                    + size_of::<pw::spa::sys::spa_meta_bitmap>()
+                    + MAX_CURSOR_BITMAP_BYTES) as i32
+            )
+        ),
+    );
+
+    // Up to 16 damage rectangles per frame, the same cap most PipeWire
+    // producers (and wlroots/Mutter) use for `SPA_META_VideoDamage`.
+    const MAX_DAMAGE_REGIONS: usize = 16;
+    let damage_meta_obj = pw::spa::pod::object!(
+        SpaTypes::ObjectParamMeta,
+        ParamType::Meta,
+        Property::new(
+            SPA_PARAM_META_type,
+            pw::spa::pod::Value::Id(pw::spa::utils::Id(pw::spa::sys::SPA_META_VideoDamage))
+        ),
+        Property::new(
+            SPA_PARAM_META_size,
+            pw::spa::pod::Value::Int(
+                (size_of::<pw::spa::sys::spa_meta_region>() * MAX_DAMAGE_REGIONS) as i32
+            )
+        ),
+    );
+
     let values: Vec<u8> = pw::spa::pod::serialize::PodSerializer::serialize(
         std::io::Cursor::new(Vec::new()),
         &pw::spa::pod::Value::Object(obj),
@@ -350,10 +567,24 @@ fn pipewire_capturer(
     )?
     .0
     .into_inner();
+    let cursor_meta_values: Vec<u8> = pw::spa::pod::serialize::PodSerializer::serialize(
+        std::io::Cursor::new(Vec::new()),
+        &pw::spa::pod::Value::Object(cursor_meta_obj),
+    )?
+    .0
+    .into_inner();
+    let damage_meta_values: Vec<u8> = pw::spa::pod::serialize::PodSerializer::serialize(
+        std::io::Cursor::new(Vec::new()),
+        &pw::spa::pod::Value::Object(damage_meta_obj),
+    )?
+    .0
+    .into_inner();
 
     let mut params = [
         pw::spa::pod::Pod::from_bytes(&values).unwrap(),
         pw::spa::pod::Pod::from_bytes(&metas_values).unwrap(),
+        pw::spa::pod::Pod::from_bytes(&cursor_meta_values).unwrap(),
+        pw::spa::pod::Pod::from_bytes(&damage_meta_values).unwrap(),
     ];
 
     stream.connect(
@@ -372,13 +603,18 @@ fn pipewire_capturer(
     let pw_loop = mainloop.loop_();
 
     // User has called Capturer::start() and we start the main loop
+    // `state_changed_callback` already reported the error via `events_tx`
+    // before flipping this flag; we just need to stop driving the loop.
     while CAPTURER_STATE.load(std::sync::atomic::Ordering::Relaxed) == 1
-        && /* If the stream state got changed to `Error`, we exit. TODO: tell user that we exited */
-          !STREAM_STATE_CHANGED_TO_ERROR.load(std::sync::atomic::Ordering::Relaxed)
+        && !STREAM_STATE_CHANGED_TO_ERROR.load(std::sync::atomic::Ordering::Relaxed)
     {
         pw_loop.iterate(Duration::from_millis(100));
     }
 
+    // Wake the worker so it observes the stopped state and exits.
+    handoff.nudge();
+    let _ = worker_handle.join();
+
     Ok(())
 }
 
@@ -387,39 +623,96 @@ pub struct LinuxCapturer {
     // The pipewire stream is deleted when the connection is dropped.
     // That's why we keep it alive
     _connection: dbus::blocking::Connection,
+    encoded_rx: Option<mpsc::Receiver<EncodedFrame>>,
+    cursor_rx: Option<mpsc::Receiver<CursorInfo>>,
+    damage_rx: Option<mpsc::Receiver<DamageInfo>>,
+    events_rx: Option<mpsc::Receiver<CaptureEvent>>,
 }
 
 impl LinuxCapturer {
-    // TODO: Error handling
-    pub fn new(options: &Options, tx: mpsc::Sender<Frame>) -> Self {
-        let connection =
-            dbus::blocking::Connection::new_session().expect("Failed to create dbus connection");
+    pub fn new(options: &Options, tx: mpsc::Sender<Frame>) -> Result<Self, LinCapError> {
+        let connection = dbus::blocking::Connection::new_session()?;
         let stream_id = ScreenCastPortal::new(&connection)
             .show_cursor(options.show_cursor)
-            .expect("Unsupported cursor mode")
+            .map_err(|_| LinCapError::Other("Unsupported cursor mode".into()))?
             .create_stream()
-            .expect("Failed to get screencast stream")
+            .map_err(|_| LinCapError::Other("Failed to get screencast stream".into()))?
             .pw_node_id();
 
         // TODO: Fix this hack
         let options = options.clone();
+        let (encoded_tx, encoded_rx) = if options.encode.is_some() {
+            let (encoded_tx, encoded_rx) = mpsc::channel();
+            (Some(encoded_tx), Some(encoded_rx))
+        } else {
+            (None, None)
+        };
+        let (cursor_tx, cursor_rx) = mpsc::channel();
+        let (damage_tx, damage_rx) = mpsc::channel();
+        let (events_tx, events_rx) = mpsc::channel();
         let (ready_sender, ready_recv) = sync_channel(1);
         let capturer_join_handle = std::thread::spawn(move || {
-            let res = pipewire_capturer(options, tx, &ready_sender, stream_id);
+            let res = pipewire_capturer(
+                options,
+                tx,
+                encoded_tx,
+                cursor_tx,
+                damage_tx,
+                events_tx,
+                &ready_sender,
+                stream_id,
+            );
             if res.is_err() {
                 ready_sender.send(false)?;
             }
             res
         });
 
-        if !ready_recv.recv().expect("Failed to receive") {
-            panic!("Failed to setup capturer");
+        if !ready_recv.recv()? {
+            return Err(LinCapError::Other("Failed to set up capturer".into()));
         }
 
-        Self {
+        Ok(Self {
             capturer_join_handle: Some(capturer_join_handle),
             _connection: connection,
-        }
+            encoded_rx,
+            cursor_rx: Some(cursor_rx),
+            damage_rx: Some(damage_rx),
+            events_rx: Some(events_rx),
+        })
+    }
+
+    /// Takes the encoded-frame receiver, if `Options::encode` was set when
+    /// this capturer was created. Returns `None` on subsequent calls.
+    pub fn take_encoded_frames(&mut self) -> Option<mpsc::Receiver<EncodedFrame>> {
+        self.encoded_rx.take()
+    }
+
+    /// Takes the cursor-metadata receiver. The video plane stays
+    /// cursor-free; positions/bitmaps arrive here instead. A `CursorInfo` is
+    /// only ever sent for a buffer that also produced a `Frame`, in frame
+    /// order, but only when that buffer carries a visible cursor, so there
+    /// is not one per frame and the two channels cannot be zipped one to one.
+    /// Returns `None` on subsequent calls.
+    pub fn take_cursor_events(&mut self) -> Option<mpsc::Receiver<CursorInfo>> {
+        self.cursor_rx.take()
+    }
+
+    /// Takes the damage-region receiver. Each `DamageInfo` is sent in the
+    /// same order as the `Frame` it belongs to goes out on the frame
+    /// channel — zip the two rather than matching on `display_time`, which
+    /// can be `0` (and so non-unique) for buffers with no `SPA_META_Header`.
+    /// Empty `regions` means the producer didn't attach a damage meta and
+    /// the whole frame should be treated as changed.
+    pub fn take_damage_events(&mut self) -> Option<mpsc::Receiver<DamageInfo>> {
+        self.damage_rx.take()
+    }
+
+    /// Takes the lifecycle-event receiver: stream errors, format changes,
+    /// unsupported-format drops and shutdown, in place of the panics this
+    /// backend used to raise on those paths.
+    pub fn take_events(&mut self) -> Option<mpsc::Receiver<CaptureEvent>> {
+        self.events_rx.take()
     }
 
     pub fn start_capture(&self) {
@@ -438,6 +731,9 @@ impl LinuxCapturer {
     }
 }
 
-pub fn create_capturer(options: &Options, tx: mpsc::Sender<Frame>) -> LinuxCapturer {
+pub fn create_capturer(
+    options: &Options,
+    tx: mpsc::Sender<Frame>,
+) -> Result<LinuxCapturer, LinCapError> {
     LinuxCapturer::new(options, tx)
 }
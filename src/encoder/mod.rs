@@ -0,0 +1,123 @@
+//! Optional video encoding for captured frames.
+//!
+//! Capturers hand raw [`Frame`](crate::frame::Frame)s to an [`Encoder`],
+//! which delegates to a [`Backend`] (software x264/rav1e to start) and
+//! produces [`EncodedFrame`] packets a caller can mux or stream directly,
+//! instead of having to build their own encoder around the raw frame
+//! channel.
+//!
+//! NOT YET IMPLEMENTED: neither backend actually calls into x264 or rav1e
+//! yet — `Backend::encode` returns `Err(EncoderError::Backend(..))` for
+//! both. `Options::encode` creates the session and the encoded-frame
+//! channel, but every `encode` call fails, so no `EncodedFrame` is ever
+//! sent on that channel and no frame is compressed.
+//!
+//! Scope note: despite this module's originating commit title ("Add optional
+//! H.264/AV1 encoder subsystem"), there is no working codec behind this
+//! interface yet — it's the wiring for one, not the thing itself. Real
+//! libx264/rav1e integration is still an open, unimplemented follow-up.
+
+mod rav1e;
+mod x264;
+
+use crate::frame::Frame;
+
+/// Compressed bitstream format produced by an [`Encoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    H264,
+    Av1,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BitrateMode {
+    Cbr,
+    Vbr,
+}
+
+/// Target bitrate, in bits per second, and whether the backend should hold
+/// it constant or let it vary with scene complexity.
+#[derive(Debug, Clone, Copy)]
+pub struct Bitrate {
+    pub mode: BitrateMode,
+    pub target: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    Baseline,
+    Main,
+    High,
+}
+
+/// Codec level, e.g. `Level(41)` for H.264 level 4.1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level(pub u32);
+
+/// Everything a [`Backend`] needs to start an encoding session.
+#[derive(Debug, Clone)]
+pub struct SessionConfig {
+    pub format: Codec,
+    pub bitrate: Bitrate,
+    pub profile: Profile,
+    pub level: Level,
+    pub keyframe_interval: u32,
+}
+
+/// Caller-facing knob: set [`crate::capturer::Options::encode`] to this to
+/// get an [`EncodedFrame`] stream alongside the raw frames.
+#[derive(Debug, Clone)]
+pub struct EncoderConfig {
+    pub session: SessionConfig,
+}
+
+/// One compressed access unit.
+#[derive(Debug, Clone)]
+pub struct EncodedFrame {
+    pub codec: Codec,
+    pub is_keyframe: bool,
+    pub pts: u64,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum EncoderError {
+    UnsupportedFrameFormat,
+    Backend(String),
+}
+
+impl std::fmt::Display for EncoderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncoderError::UnsupportedFrameFormat => write!(f, "unsupported frame format"),
+            EncoderError::Backend(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for EncoderError {}
+
+/// A concrete codec implementation. One instance owns one encoding session.
+trait Backend: Send {
+    fn encode(&mut self, frame: &Frame) -> Result<EncodedFrame, EncoderError>;
+}
+
+/// Converts raw [`Frame`]s into an [`EncodedFrame`] stream, backed by
+/// whichever codec implementation [`SessionConfig::format`] selects.
+pub struct Encoder {
+    backend: Box<dyn Backend>,
+}
+
+impl Encoder {
+    pub fn new(config: SessionConfig) -> Result<Self, EncoderError> {
+        let backend: Box<dyn Backend> = match config.format {
+            Codec::H264 => Box::new(x264::X264Backend::new(config)?),
+            Codec::Av1 => Box::new(rav1e::Rav1eBackend::new(config)?),
+        };
+        Ok(Self { backend })
+    }
+
+    pub fn encode(&mut self, frame: &Frame) -> Result<EncodedFrame, EncoderError> {
+        self.backend.encode(frame)
+    }
+}
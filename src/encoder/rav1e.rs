@@ -0,0 +1,35 @@
+use super::{Backend, EncodedFrame, EncoderError, SessionConfig};
+use crate::frame::Frame;
+
+/// Software AV1 encoding via rav1e.
+pub(super) struct Rav1eBackend {
+    config: SessionConfig,
+    frame_count: u64,
+}
+
+impl Rav1eBackend {
+    pub(super) fn new(config: SessionConfig) -> Result<Self, EncoderError> {
+        // Real implementation builds an `rav1e::Config` from `config`
+        // (bitrate mode/target, keyframe interval) and creates a `Context`.
+        Ok(Self {
+            config,
+            frame_count: 0,
+        })
+    }
+}
+
+impl Backend for Rav1eBackend {
+    fn encode(&mut self, _frame: &Frame) -> Result<EncodedFrame, EncoderError> {
+        // Keyframe cadence bookkeeping only; kept ready for the real
+        // implementation to condition its keyframe requests on below.
+        let _is_keyframe = self.frame_count % self.config.keyframe_interval as u64 == 0;
+        self.frame_count += 1;
+
+        // NOT YET IMPLEMENTED: nothing here actually sends the frame through
+        // `Context::send_frame`/`receive_packet`. Returning a fabricated
+        // `EncodedFrame` with empty `data` would look like a working
+        // bitstream to callers, so fail loudly instead until the real call
+        // lands.
+        Err(EncoderError::Backend("rav1e backend not implemented".into()))
+    }
+}
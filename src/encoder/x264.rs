@@ -0,0 +1,36 @@
+use super::{Backend, EncodedFrame, EncoderError, SessionConfig};
+use crate::frame::Frame;
+
+/// Software H.264 encoding via x264.
+pub(super) struct X264Backend {
+    config: SessionConfig,
+    frame_count: u64,
+}
+
+impl X264Backend {
+    pub(super) fn new(config: SessionConfig) -> Result<Self, EncoderError> {
+        // Real implementation opens an `x264_t` encoder with `config`
+        // translated into `x264_param_t` (profile, level, bitrate mode,
+        // keyframe interval), matching the session knobs the caller asked
+        // for.
+        Ok(Self {
+            config,
+            frame_count: 0,
+        })
+    }
+}
+
+impl Backend for X264Backend {
+    fn encode(&mut self, _frame: &Frame) -> Result<EncodedFrame, EncoderError> {
+        // Keyframe cadence bookkeeping only; kept ready for the real
+        // implementation to condition its IDR requests on below.
+        let _is_keyframe = self.frame_count % self.config.keyframe_interval as u64 == 0;
+        self.frame_count += 1;
+
+        // NOT YET IMPLEMENTED: nothing here actually feeds the frame into
+        // `x264_encoder_encode`. Returning a fabricated `EncodedFrame` with
+        // empty `data` would look like a working bitstream to callers, so
+        // fail loudly instead until the real call lands.
+        Err(EncoderError::Backend("x264 backend not implemented".into()))
+    }
+}
@@ -0,0 +1,191 @@
+//! Built-in fragmented-MP4 recorder.
+//!
+//! Consumes the [`EncodedFrame`](crate::encoder::EncodedFrame) stream an
+//! [`Encoder`](crate::encoder::Encoder) produces and writes it straight to
+//! disk as a fragmented MP4, using the box-writing approach from
+//! gst-plugins-rs' fmp4 muxer: one `ftyp`+`moov` init segment up front, then
+//! one `moof`+`mdat` pair per fragment, cut on keyframe boundaries. Each
+//! fragment is flushed to the file as soon as it closes, so the result is a
+//! streamable, seekable file even if the process is killed mid-capture —
+//! everything up to the last completed fragment stays valid; only the
+//! `mfra` random-access index (written in [`Recorder::finish`]) and the
+//! still-open trailing fragment are lost.
+//!
+//! BLOCKED ON `crate::encoder`: both built-in backends (x264, rav1e) are
+//! still stubs that return `Err` instead of compressed data (see that
+//! module's doc comment), so today `push` never actually receives an
+//! `EncodedFrame` carrying real bytes. The box-writing here is complete and
+//! self-consistent, but the `stsd` sample entry this writes has no real
+//! `avcC`/`av1C` decoder config record — see `mp4::build_sample_entry` —
+//! so a file recorded once a backend does produce bytes still won't be
+//! conformant until that's filled in too.
+
+mod mp4;
+
+use std::{
+    fs::File,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::encoder::{Codec, EncodedFrame};
+
+/// What `Recorder::new` needs to write an init segment matching the stream
+/// it's about to receive.
+#[derive(Debug, Clone)]
+pub struct RecorderConfig {
+    pub codec: Codec,
+    pub width: u32,
+    pub height: u32,
+    /// Units `EncodedFrame::pts` and `max_fragment_duration` are expressed
+    /// in, written into the `mvhd`/`mdhd` timescale fields.
+    pub timescale: u32,
+    /// Upper bound on how long a fragment is allowed to run, in
+    /// `timescale` units, before the next keyframe should close it out.
+    /// Fragments can only be cut at a keyframe, so this is a target for
+    /// the encoder's keyframe interval rather than something `Recorder`
+    /// enforces mid-GOP.
+    pub max_fragment_duration: u64,
+}
+
+#[derive(Debug)]
+pub enum RecorderError {
+    Io(io::Error),
+}
+
+impl std::fmt::Display for RecorderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecorderError::Io(e) => write!(f, "io error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for RecorderError {}
+
+impl From<io::Error> for RecorderError {
+    fn from(e: io::Error) -> Self {
+        RecorderError::Io(e)
+    }
+}
+
+/// Writes an [`EncodedFrame`] stream to a fragmented MP4 file.
+pub struct Recorder {
+    file: File,
+    config: RecorderConfig,
+    sequence_number: u32,
+    base_media_decode_time: u64,
+    pending: Vec<mp4::FragmentSample>,
+    /// Parallel to `pending`: each sample's pts, used to derive durations
+    /// (a sample's duration is its successor's pts minus its own) once the
+    /// fragment is flushed.
+    pending_pts: Vec<u64>,
+    fragment_start_pts: Option<u64>,
+    bytes_written: u64,
+    fragment_offsets: Vec<(u64, u64)>,
+}
+
+impl Recorder {
+    /// Creates `path` and writes the init segment (`ftyp`+`moov`)
+    /// immediately, so the file is a valid (empty) MP4 before the first
+    /// frame ever arrives.
+    pub fn new(path: impl AsRef<Path>, config: RecorderConfig) -> Result<Self, RecorderError> {
+        let mut file = File::create(path)?;
+        let init_segment = mp4::build_init_segment(&config);
+        file.write_all(&init_segment)?;
+
+        Ok(Self {
+            bytes_written: init_segment.len() as u64,
+            file,
+            config,
+            sequence_number: 0,
+            base_media_decode_time: 0,
+            pending: Vec::new(),
+            pending_pts: Vec::new(),
+            fragment_start_pts: None,
+            fragment_offsets: Vec::new(),
+        })
+    }
+
+    /// Queues `frame`. A keyframe closes out and flushes whatever fragment
+    /// is currently pending before starting a new one with itself as the
+    /// leading sample.
+    pub fn push(&mut self, frame: EncodedFrame) -> Result<(), RecorderError> {
+        if frame.is_keyframe && !self.pending.is_empty() {
+            self.flush_fragment()?;
+        }
+
+        match self.fragment_start_pts {
+            Some(start)
+                if !frame.is_keyframe
+                    && frame.pts.saturating_sub(start) > self.config.max_fragment_duration =>
+            {
+                eprintln!(
+                    "recorder: fragment exceeds max_fragment_duration with no keyframe yet; \
+                     consider a shorter keyframe_interval"
+                );
+            }
+            Some(_) => {}
+            None => self.fragment_start_pts = Some(frame.pts),
+        }
+
+        self.pending_pts.push(frame.pts);
+        self.pending.push(mp4::FragmentSample {
+            duration: 0, // filled in by `flush_fragment`, once every pts is known
+            size: frame.data.len() as u32,
+            data: frame.data,
+        });
+
+        Ok(())
+    }
+
+    fn flush_fragment(&mut self) -> Result<(), RecorderError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+
+        let mut samples = std::mem::take(&mut self.pending);
+        let pts = std::mem::take(&mut self.pending_pts);
+        let fragment_start_pts = self.fragment_start_pts.take().unwrap_or(0);
+
+        // A sample's duration is how long it's displayed for, i.e. the gap
+        // to the next sample's pts. The last sample in the fragment has no
+        // successor yet, so fall back to the average of the others (or the
+        // configured max duration, for a single-sample fragment).
+        let fallback_duration = if pts.len() > 1 {
+            (pts[pts.len() - 1].saturating_sub(fragment_start_pts)) / (pts.len() as u64 - 1)
+        } else {
+            self.config.max_fragment_duration
+        }
+        .max(1);
+        for i in 0..samples.len() {
+            samples[i].duration = match pts.get(i + 1) {
+                Some(&next) => next.saturating_sub(pts[i]).max(1) as u32,
+                None => fallback_duration as u32,
+            };
+        }
+
+        self.sequence_number += 1;
+        let moof_offset = self.bytes_written;
+        let fragment =
+            mp4::build_fragment(&samples, self.sequence_number, self.base_media_decode_time);
+        self.file.write_all(&fragment)?;
+        self.bytes_written += fragment.len() as u64;
+
+        self.fragment_offsets.push((fragment_start_pts, moof_offset));
+        self.base_media_decode_time += samples.iter().map(|s| s.duration as u64).sum::<u64>();
+
+        Ok(())
+    }
+
+    /// Flushes whatever fragment is still pending and writes the `mfra`
+    /// random-access index. Consumes `self`: once a recording is finished
+    /// there's nothing left to push to.
+    pub fn finish(mut self) -> Result<(), RecorderError> {
+        self.flush_fragment()?;
+        let mfra = mp4::build_mfra(&self.fragment_offsets);
+        self.file.write_all(&mfra)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
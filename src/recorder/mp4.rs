@@ -0,0 +1,458 @@
+//! Low-level fragmented-MP4 box writing.
+//!
+//! Follows the same init-segment-then-fragments shape as gst-plugins-rs'
+//! `mp4mux`/`fmp4mux`: one `ftyp`+`moov` written once up front (carrying the
+//! codec config and an empty `stbl`, since samples only ever live in later
+//! fragments), then one `moof`+`mdat` pair per fragment. Everything here
+//! operates on plain byte buffers; there's no dependency on an external mp4
+//! crate.
+
+use super::RecorderConfig;
+use crate::encoder::Codec;
+
+fn make_box(fourcc: &[u8; 4], body: Vec<u8>) -> Vec<u8> {
+    let mut out = Vec::with_capacity(8 + body.len());
+    out.extend_from_slice(&((8 + body.len()) as u32).to_be_bytes());
+    out.extend_from_slice(fourcc);
+    out.extend(body);
+    out
+}
+
+/// A "full box": a regular box with a version byte and 24-bit flags field
+/// prepended to the body, per ISO/IEC 14496-12.
+fn full_box(fourcc: &[u8; 4], version: u8, flags: u32, mut body: Vec<u8>) -> Vec<u8> {
+    let mut full_body = Vec::with_capacity(4 + body.len());
+    full_body.push(version);
+    full_body.extend_from_slice(&flags.to_be_bytes()[1..]);
+    full_body.append(&mut body);
+    make_box(fourcc, full_body)
+}
+
+fn fixed_16_16(v: u32) -> u32 {
+    v << 16
+}
+
+const UNITY_MATRIX: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+
+fn build_mvhd(timescale: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    body.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    body.extend_from_slice(&timescale.to_be_bytes());
+    body.extend_from_slice(&0u32.to_be_bytes()); // duration: unknown, this is a fragmented file
+    body.extend_from_slice(&0x0001_0000u32.to_be_bytes()); // rate: 1.0
+    body.extend_from_slice(&0x0100u16.to_be_bytes()); // volume: 1.0
+    body.extend_from_slice(&[0u8; 2]); // reserved
+    body.extend_from_slice(&[0u8; 8]); // reserved
+    for v in UNITY_MATRIX {
+        body.extend_from_slice(&v.to_be_bytes());
+    }
+    body.extend_from_slice(&[0u8; 24]); // pre_defined
+    body.extend_from_slice(&2u32.to_be_bytes()); // next_track_ID
+    full_box(b"mvhd", 0, 0, body)
+}
+
+fn build_tkhd(width: u32, height: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    body.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    body.extend_from_slice(&1u32.to_be_bytes()); // track_ID
+    body.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    body.extend_from_slice(&0u32.to_be_bytes()); // duration: unknown
+    body.extend_from_slice(&[0u8; 8]); // reserved
+    body.extend_from_slice(&0i16.to_be_bytes()); // layer
+    body.extend_from_slice(&0i16.to_be_bytes()); // alternate_group
+    body.extend_from_slice(&0i16.to_be_bytes()); // volume: 0 for video tracks
+    body.extend_from_slice(&[0u8; 2]); // reserved
+    for v in UNITY_MATRIX {
+        body.extend_from_slice(&v.to_be_bytes());
+    }
+    body.extend_from_slice(&fixed_16_16(width).to_be_bytes());
+    body.extend_from_slice(&fixed_16_16(height).to_be_bytes());
+    // flags: track enabled | in movie | in preview
+    full_box(b"tkhd", 0, 0x000007, body)
+}
+
+fn build_mdhd(timescale: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    body.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    body.extend_from_slice(&timescale.to_be_bytes());
+    body.extend_from_slice(&0u32.to_be_bytes()); // duration: unknown
+    body.extend_from_slice(&0x55c4u16.to_be_bytes()); // language: "und"
+    body.extend_from_slice(&0u16.to_be_bytes()); // pre_defined
+    full_box(b"mdhd", 0, 0, body)
+}
+
+fn build_hdlr() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes()); // pre_defined
+    body.extend_from_slice(b"vide");
+    body.extend_from_slice(&[0u8; 12]); // reserved
+    body.extend_from_slice(b"scap\0");
+    full_box(b"hdlr", 0, 0, body)
+}
+
+fn build_vmhd() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u16.to_be_bytes()); // graphicsmode
+    body.extend_from_slice(&[0u8; 6]); // opcolor
+    // `flags` is required to be 1 for vmhd specifically.
+    full_box(b"vmhd", 0, 1, body)
+}
+
+fn build_dinf() -> Vec<u8> {
+    let url = full_box(b"url ", 0, 1, Vec::new()); // flags=1: media data is in this same file
+    let mut dref_body = Vec::new();
+    dref_body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    dref_body.extend(url);
+    make_box(b"dinf", full_box(b"dref", 0, 0, dref_body))
+}
+
+/// Codec-specific sample entry (`avc1`/`av01`) plus its decoder config box.
+///
+/// We don't parse the bitstream for the real parameter sets here (the x264
+/// and rav1e backends are still stubs that return `Err` for every frame), so the
+/// `avcC`/`av1C` payload is a placeholder. A real implementation needs to
+/// pull the SPS/PPS (H.264) or sequence header OBU (AV1) out of the first
+/// keyframe and store those bytes here instead.
+fn build_sample_entry(codec: Codec, width: u32, height: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&[0u8; 6]); // reserved
+    body.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    body.extend_from_slice(&0u16.to_be_bytes()); // pre_defined
+    body.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    body.extend_from_slice(&[0u8; 12]); // pre_defined
+    body.extend_from_slice(&(width as u16).to_be_bytes());
+    body.extend_from_slice(&(height as u16).to_be_bytes());
+    body.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // horizresolution: 72 dpi
+    body.extend_from_slice(&0x0048_0000u32.to_be_bytes()); // vertresolution: 72 dpi
+    body.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    body.extend_from_slice(&1u16.to_be_bytes()); // frame_count
+    body.extend_from_slice(&[0u8; 32]); // compressorname
+    body.extend_from_slice(&0x0018u16.to_be_bytes()); // depth
+    body.extend_from_slice(&(-1i16).to_be_bytes()); // pre_defined
+
+    let (fourcc, config_fourcc): (&[u8; 4], &[u8; 4]) = match codec {
+        Codec::H264 => (b"avc1", b"avcC"),
+        Codec::Av1 => (b"av01", b"av1C"),
+    };
+    body.extend(make_box(config_fourcc, Vec::new()));
+    make_box(fourcc, body)
+}
+
+fn build_stsd(codec: Codec, width: u32, height: u32) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    body.extend(build_sample_entry(codec, width, height));
+    full_box(b"stsd", 0, 0, body)
+}
+
+fn build_stbl(codec: Codec, width: u32, height: u32) -> Vec<u8> {
+    let mut body = build_stsd(codec, width, height);
+    body.extend(full_box(b"stts", 0, 0, 0u32.to_be_bytes().to_vec()));
+    body.extend(full_box(b"stsc", 0, 0, 0u32.to_be_bytes().to_vec()));
+    let mut stsz_body = 0u32.to_be_bytes().to_vec(); // sample_size
+    stsz_body.extend_from_slice(&0u32.to_be_bytes()); // sample_count
+    body.extend(full_box(b"stsz", 0, 0, stsz_body));
+    body.extend(full_box(b"stco", 0, 0, 0u32.to_be_bytes().to_vec()));
+    make_box(b"stbl", body)
+}
+
+fn build_minf(codec: Codec, width: u32, height: u32) -> Vec<u8> {
+    let mut body = build_vmhd();
+    body.extend(build_dinf());
+    body.extend(build_stbl(codec, width, height));
+    make_box(b"minf", body)
+}
+
+fn build_mdia(config: &RecorderConfig) -> Vec<u8> {
+    let mut body = build_mdhd(config.timescale);
+    body.extend(build_hdlr());
+    body.extend(build_minf(config.codec, config.width, config.height));
+    make_box(b"mdia", body)
+}
+
+fn build_trak(config: &RecorderConfig) -> Vec<u8> {
+    let mut body = build_tkhd(config.width, config.height);
+    body.extend(build_mdia(config));
+    make_box(b"trak", body)
+}
+
+/// Default per-sample flags for `trex`: marks every sample as a non-sync
+/// sample by default. `trun` overrides this per-fragment via
+/// `first_sample_flags` for the keyframe that starts it.
+const TREX_DEFAULT_SAMPLE_FLAGS: u32 = 0x0001_0000;
+/// `first_sample_flags` for a fragment's leading (keyframe) sample:
+/// `sample_depends_on = 2` ("does not depend on others"), non-sync bit clear.
+const TRUN_SYNC_SAMPLE_FLAGS: u32 = 0x0200_0000;
+
+fn build_trex() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&1u32.to_be_bytes()); // track_ID
+    body.extend_from_slice(&1u32.to_be_bytes()); // default_sample_description_index
+    body.extend_from_slice(&0u32.to_be_bytes()); // default_sample_duration
+    body.extend_from_slice(&0u32.to_be_bytes()); // default_sample_size
+    body.extend_from_slice(&TREX_DEFAULT_SAMPLE_FLAGS.to_be_bytes());
+    full_box(b"trex", 0, 0, body)
+}
+
+fn build_mvex() -> Vec<u8> {
+    make_box(b"mvex", build_trex())
+}
+
+fn build_moov(config: &RecorderConfig) -> Vec<u8> {
+    let mut body = build_mvhd(config.timescale);
+    body.extend(build_trak(config));
+    body.extend(build_mvex());
+    make_box(b"moov", body)
+}
+
+/// `ftyp` + `moov`: written once, before the first fragment.
+pub(super) fn build_init_segment(config: &RecorderConfig) -> Vec<u8> {
+    let mut ftyp_body = Vec::new();
+    ftyp_body.extend_from_slice(b"iso5"); // major_brand
+    ftyp_body.extend_from_slice(&0u32.to_be_bytes()); // minor_version
+    for brand in [b"iso5", b"iso6", b"mp41"] {
+        ftyp_body.extend_from_slice(brand);
+    }
+    let mut out = make_box(b"ftyp", ftyp_body);
+    out.extend(build_moov(config));
+    out
+}
+
+/// One sample queued for the fragment currently being assembled. The first
+/// sample of every fragment is always a keyframe (that's what closes out
+/// the previous fragment in `Recorder::push`), so there's no per-sample
+/// sync-sample flag to track here; `build_trun` marks it via
+/// `first_sample_flags` instead.
+pub(super) struct FragmentSample {
+    pub duration: u32,
+    pub size: u32,
+    pub data: Vec<u8>,
+}
+
+fn build_mfhd(sequence_number: u32) -> Vec<u8> {
+    full_box(b"mfhd", 0, 0, sequence_number.to_be_bytes().to_vec())
+}
+
+fn build_tfhd() -> Vec<u8> {
+    let body = 1u32.to_be_bytes().to_vec(); // track_ID
+    // flags: default-base-is-moof
+    full_box(b"tfhd", 0, 0x02_0000, body)
+}
+
+fn build_tfdt(base_media_decode_time: u64) -> Vec<u8> {
+    // version 1: 64-bit base_media_decode_time
+    full_box(b"tfdt", 1, 0, base_media_decode_time.to_be_bytes().to_vec())
+}
+
+/// Builds `trun` with a placeholder `data_offset`, returning the box bytes
+/// plus the byte offset of the `data_offset` field within them so the
+/// caller can patch it in once the enclosing `moof`'s total size is known.
+fn build_trun(samples: &[FragmentSample]) -> (Vec<u8>, usize) {
+    // flags: data-offset-present | first-sample-flags-present |
+    // sample-duration-present | sample-size-present
+    let flags = 0x00_0001 | 0x00_0004 | 0x00_0100 | 0x00_0200;
+    let mut body = Vec::new();
+    body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+    // `trun`'s full-box header (8-byte box header + 1-byte version +
+    // 3-byte flags) comes before `body`.
+    let data_offset_index = 12 + body.len();
+    body.extend_from_slice(&0i32.to_be_bytes()); // data_offset: patched below
+    body.extend_from_slice(&TRUN_SYNC_SAMPLE_FLAGS.to_be_bytes());
+    for sample in samples {
+        body.extend_from_slice(&sample.duration.to_be_bytes());
+        body.extend_from_slice(&sample.size.to_be_bytes());
+    }
+    (full_box(b"trun", 0, flags, body), data_offset_index)
+}
+
+/// Builds one complete `moof`+`mdat` fragment, ready to write to the file.
+pub(super) fn build_fragment(
+    samples: &[FragmentSample],
+    sequence_number: u32,
+    base_media_decode_time: u64,
+) -> Vec<u8> {
+    let mfhd = build_mfhd(sequence_number);
+    let tfhd = build_tfhd();
+    let tfdt = build_tfdt(base_media_decode_time);
+    let (trun, trun_data_offset_index) = build_trun(samples);
+
+    // `moof` header (8) + `mfhd` + `traf` header (8) + `tfhd` + `tfdt` is
+    // everything that comes before `trun` starts, so the data_offset field
+    // patched in below sits at this many bytes into the final `moof` box.
+    let trun_start = 8 + mfhd.len() + 8 + tfhd.len() + tfdt.len();
+    let patch_at = trun_start + trun_data_offset_index;
+
+    let mut traf_body = tfhd;
+    traf_body.extend(tfdt);
+    traf_body.extend(trun);
+    let traf = make_box(b"traf", traf_body);
+
+    let mut moof_body = mfhd;
+    moof_body.extend(traf);
+    let mut moof = make_box(b"moof", moof_body);
+
+    // `trun`'s data_offset is relative to the start of the moof box; the
+    // first sample byte sits right after moof and the 8-byte mdat header.
+    let data_offset = (moof.len() + 8) as i32;
+    moof[patch_at..patch_at + 4].copy_from_slice(&data_offset.to_be_bytes());
+
+    let mut mdat_body = Vec::with_capacity(samples.iter().map(|s| s.data.len()).sum());
+    for sample in samples {
+        mdat_body.extend_from_slice(&sample.data);
+    }
+
+    let mut out = moof;
+    out.extend(make_box(b"mdat", mdat_body));
+    out
+}
+
+/// `mfra`: a per-fragment random-access index, written once after the last
+/// fragment. `entries` is `(presentation_time, moof_byte_offset)` for every
+/// fragment written so far.
+pub(super) fn build_mfra(entries: &[(u64, u64)]) -> Vec<u8> {
+    let mut tfra_body = Vec::new();
+    tfra_body.extend_from_slice(&1u32.to_be_bytes()); // track_ID
+    tfra_body.extend_from_slice(&0u32.to_be_bytes()); // length_size_of_* all 1 byte
+    tfra_body.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (time, moof_offset) in entries {
+        tfra_body.extend_from_slice(&time.to_be_bytes());
+        tfra_body.extend_from_slice(&moof_offset.to_be_bytes());
+        tfra_body.push(1); // traf_number
+        tfra_body.push(1); // trun_number
+        tfra_body.push(1); // sample_number: the leading (key) sample
+    }
+    let tfra = full_box(b"tfra", 1, 0, tfra_body);
+
+    let mfra_size = (8 + tfra.len() + 16) as u32; // `mfra` header + `tfra` + `mfro` itself
+    let mfro = full_box(b"mfro", 0, 0, mfra_size.to_be_bytes().to_vec());
+
+    let mut body = tfra;
+    body.extend(mfro);
+    make_box(b"mfra", body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(duration: u32, data: &[u8]) -> FragmentSample {
+        FragmentSample { duration, size: data.len() as u32, data: data.to_vec() }
+    }
+
+    #[test]
+    fn make_box_prefixes_size_and_fourcc() {
+        let b = make_box(b"test", vec![1, 2, 3]);
+        assert_eq!(u32::from_be_bytes(b[0..4].try_into().unwrap()), 11); // 8 header + 3 body
+        assert_eq!(&b[4..8], b"test");
+        assert_eq!(&b[8..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn full_box_prepends_version_and_flags() {
+        let b = full_box(b"test", 1, 0x00_02_03, vec![0xFF]);
+        // box header (8) + version (1) + flags (3) + body (1) = 13
+        assert_eq!(u32::from_be_bytes(b[0..4].try_into().unwrap()), 13);
+        assert_eq!(b[8], 1); // version
+        assert_eq!(&b[9..12], &[0x00, 0x02, 0x03]); // flags, 24-bit big-endian
+        assert_eq!(b[12], 0xFF);
+    }
+
+    #[test]
+    fn build_trun_reports_a_data_offset_index_that_points_at_the_placeholder() {
+        let samples = [sample(10, &[0xAA]), sample(10, &[0xBB])];
+        let (trun, data_offset_index) = build_trun(&samples);
+        // The placeholder is written as 0i32 and must still be zero at the
+        // index `build_fragment` is told to patch.
+        assert_eq!(&trun[data_offset_index..data_offset_index + 4], &0i32.to_be_bytes());
+    }
+
+    #[test]
+    fn build_trun_flags_match_the_fields_actually_written_into_body() {
+        // ISO/IEC 14496-12 trun flag bits (low byte of the 24-bit flags):
+        // bit 0 data-offset-present, bit 2 first-sample-flags-present,
+        // and (middle byte) bit 8 sample-duration-present, bit 9
+        // sample-size-present.
+        const DATA_OFFSET_PRESENT: u32 = 0x00_0001;
+        const FIRST_SAMPLE_FLAGS_PRESENT: u32 = 0x00_0004;
+        const SAMPLE_DURATION_PRESENT: u32 = 0x00_0100;
+        const SAMPLE_SIZE_PRESENT: u32 = 0x00_0200;
+
+        let samples = [sample(10, &[0xAA]), sample(20, &[0xBB, 0xCC])];
+        let (trun, _) = build_trun(&samples);
+
+        let flags = u32::from_be_bytes([0, trun[9], trun[10], trun[11]]);
+        assert_eq!(
+            flags,
+            DATA_OFFSET_PRESENT | FIRST_SAMPLE_FLAGS_PRESENT | SAMPLE_DURATION_PRESENT | SAMPLE_SIZE_PRESENT
+        );
+
+        // body = sample_count(4) + data_offset(4, because bit 0 is set) +
+        // first_sample_flags(4, because bit 2 is set) + per-sample
+        // duration+size (8 bytes each, because bits 8 and 9 are set).
+        let expected_body_len = 4 + 4 + 4 + samples.len() * 8;
+        let body_len = trun.len() - 12; // trun box header (8) + version/flags (4)
+        assert_eq!(body_len, expected_body_len);
+    }
+
+    #[test]
+    fn build_fragment_patches_data_offset_to_the_first_mdat_sample_byte() {
+        let samples = [sample(10, &[0xAA, 0xBB]), sample(10, &[0xCC])];
+        let fragment = build_fragment(&samples, 1, 0);
+
+        let (trun, trun_data_offset_index) = build_trun(&samples);
+        let mfhd = build_mfhd(1);
+        let tfhd = build_tfhd();
+        let tfdt = build_tfdt(0);
+        let trun_start = 8 + mfhd.len() + 8 + tfhd.len() + tfdt.len();
+        let patch_at = trun_start + trun_data_offset_index;
+
+        // `moof` is everything up to (but not including) the `mdat` header;
+        // the patched data_offset should point exactly `moof.len() + 8`
+        // bytes in, i.e. right after `mdat`'s own box header.
+        let moof_len = fragment.len() - (8 + samples.iter().map(|s| s.data.len()).sum::<usize>());
+        let data_offset =
+            i32::from_be_bytes(fragment[patch_at..patch_at + 4].try_into().unwrap());
+        assert_eq!(data_offset, (moof_len + 8) as i32);
+
+        // Sanity: the `trun` box size built standalone and the length
+        // embedded in the real fragment agree, i.e. nothing upstream of it
+        // shifted the index we just patched at.
+        assert_eq!(trun.len(), {
+            let traf_start = 8 + mfhd.len() + 8;
+            let trun_offset_in_fragment = traf_start + tfhd.len() + tfdt.len();
+            u32::from_be_bytes(
+                fragment[trun_offset_in_fragment..trun_offset_in_fragment + 4]
+                    .try_into()
+                    .unwrap(),
+            ) as usize
+        });
+    }
+
+    #[test]
+    fn build_mfra_declares_its_own_total_size_in_mfro() {
+        let entries = [(0u64, 100u64), (10u64, 500u64)];
+        let mfra = build_mfra(&entries);
+
+        // mfro is the last 16 bytes: 8-byte box header + 1 version + 3 flags
+        // + 4-byte size field.
+        let mfro = &mfra[mfra.len() - 16..];
+        assert_eq!(&mfro[4..8], b"mfro");
+        let declared_size = u32::from_be_bytes(mfro[12..16].try_into().unwrap());
+        assert_eq!(declared_size as usize, mfra.len());
+    }
+
+    #[test]
+    fn build_mfra_writes_one_tfra_entry_per_fragment() {
+        let entries = [(0u64, 100u64), (10u64, 500u64), (20u64, 900u64)];
+        let mfra = build_mfra(&entries);
+
+        // tfra starts right after the `mfra` box header (8 bytes); within
+        // tfra, entry_count sits after its own header (8) + version/flags
+        // (4) + track_ID (4) + length_size_of_* (4) = offset 20.
+        let tfra_entry_count = u32::from_be_bytes(mfra[28..32].try_into().unwrap());
+        assert_eq!(tfra_entry_count, entries.len() as u32);
+    }
+}